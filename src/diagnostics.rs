@@ -26,6 +26,7 @@ pub struct DiagnosticsConfig {
   pub recursion_cycle: Severity,
   pub missing_main: Severity,
   pub import_shadow: Severity,
+  pub hole: Severity,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +67,7 @@ pub enum WarningType {
   RecursionCycle,
   MissingMain,
   ImportShadow,
+  Hole,
 }
 
 impl Diagnostics {
@@ -337,6 +339,7 @@ impl DiagnosticsConfig {
       repeated_bind: severity,
       recursion_cycle: severity,
       import_shadow: severity,
+      hole: severity,
       // Should only be changed manually, as a missing main is always a error to hvm
       missing_main: Severity::Error,
       verbose,
@@ -353,6 +356,7 @@ impl DiagnosticsConfig {
       WarningType::UnreachableMatch => self.unreachable_match,
       WarningType::MissingMain => self.missing_main,
       WarningType::ImportShadow => self.import_shadow,
+      WarningType::Hole => self.hole,
     }
   }
 }
@@ -396,6 +400,7 @@ impl Diagnostic {
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, PartialOrd, Ord, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextLocation {
   pub line: usize,
   pub char: usize,
@@ -427,6 +432,7 @@ impl TextLocation {
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, PartialOrd, Ord, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextSpan {
   pub start: TextLocation,
   pub end: TextLocation,
@@ -0,0 +1,72 @@
+use crate::{
+  diagnostics::{Diagnostics, WarningType},
+  fun::{Ctx, Name, Term},
+  maybe_grow,
+};
+
+#[derive(Debug, Clone)]
+pub struct HoleWarn {
+  nam: Name,
+  scope: Vec<Name>,
+}
+
+impl Ctx<'_> {
+  /// Reports every `?name` hole in the program, together with the variables in scope where it is,
+  /// and replaces the holes with erasers so that the program can still be compiled.
+  ///
+  /// Holes let a program be checked while parts of it are still unwritten.
+  /// They're warnings by default, but can be turned into errors.
+  /// Must be called after `fix_match_terms`, so that the binds of match arms are explicit,
+  /// and after `lift_local_defs`, so that the holes in local definitions are found too.
+  pub fn check_holes(&mut self) -> Result<(), Diagnostics> {
+    for (def_name, def) in self.book.defs.iter_mut() {
+      let mut warns = vec![];
+      for rule in def.rules.iter_mut() {
+        let mut scope = rule.pats.iter().flat_map(|pat| pat.binds()).flatten().cloned().collect::<Vec<_>>();
+        rule.body.check_holes(&mut scope, &mut warns);
+      }
+
+      for warn in warns {
+        self.info.add_function_warning(warn, WarningType::Hole, def_name.clone(), def.source.clone());
+      }
+    }
+
+    self.info.fatal(())
+  }
+}
+
+impl Term {
+  pub fn check_holes(&mut self, scope: &mut Vec<Name>, warns: &mut Vec<HoleWarn>) {
+    maybe_grow(|| {
+      if let Term::Hole { nam } = self {
+        // Show each variable once, with the innermost binds first.
+        let mut vars: Vec<Name> = vec![];
+        for var in scope.iter().rev().filter(|var| !var.is_generated()) {
+          if !vars.contains(var) {
+            vars.push(var.clone());
+          }
+        }
+        warns.push(HoleWarn { nam: std::mem::take(nam), scope: vars });
+        *self = Term::Era;
+        return;
+      }
+      for (child, binds) in self.children_mut_with_binds() {
+        let len = scope.len();
+        scope.extend(binds.flatten().cloned());
+        child.check_holes(scope, warns);
+        scope.truncate(len);
+      }
+    })
+  }
+}
+
+impl std::fmt::Display for HoleWarn {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.scope.is_empty() {
+      write!(f, "Found hole '?{}'. There are no variables in scope.", self.nam)
+    } else {
+      let vars = self.scope.iter().map(|var| format!("'{var}'")).collect::<Vec<_>>().join(", ");
+      write!(f, "Found hole '?{}'. Variables in scope: {vars}.", self.nam)
+    }
+  }
+}
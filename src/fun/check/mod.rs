@@ -1,4 +1,6 @@
 pub mod check_untyped;
+pub mod holes;
+pub mod repeated_binds;
 pub mod set_entrypoint;
 pub mod shared_names;
 pub mod type_check;
@@ -0,0 +1,52 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Name, Term},
+  maybe_grow,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct RepeatedBindWarn(Name);
+
+impl Ctx<'_> {
+  /// Checks for variables that are bound more than once by the same lambda or let pattern,
+  /// like `λ(a, a) a` or `let {a a} = x; a`.
+  ///
+  /// Only one of the binds can be referenced, so this is almost always a mistake.
+  pub fn check_repeated_binds(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      let mut warns = vec![];
+      for rule in &def.rules {
+        rule.body.check_repeated_binds(&mut warns);
+      }
+
+      for warn in warns {
+        self.info.add_function_warning(warn, WarningType::RepeatedBind, def_name.clone(), def.source.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  pub fn check_repeated_binds(&self, warns: &mut Vec<RepeatedBindWarn>) {
+    maybe_grow(|| {
+      if let Term::Lam { pat, .. } | Term::Let { pat, .. } = self {
+        let mut binds = HashSet::new();
+        for nam in pat.binds().flatten() {
+          if !binds.insert(nam) {
+            warns.push(RepeatedBindWarn(nam.clone()));
+          }
+        }
+      }
+      for child in self.children() {
+        child.check_repeated_binds(warns);
+      }
+    })
+  }
+}
+
+impl std::fmt::Display for RepeatedBindWarn {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Repeated bind in pattern: '{}'.", self.0)
+  }
+}
@@ -6,7 +6,8 @@ use crate::{
 
 #[derive(Debug, Clone)]
 pub enum EntryErr {
-  NotFound(Name),
+  /// The entry point wasn't found, with the name of a similar definition, if any.
+  NotFound(Name, Option<Name>),
   Multiple(Vec<Name>),
   MultipleRules,
 }
@@ -44,7 +45,8 @@ impl Ctx<'_> {
 
       (None, None, None) => {
         let entrypoint = self.book.entrypoint.clone().unwrap_or(Name::new(ENTRY_POINT));
-        self.info.add_book_warning(EntryErr::NotFound(entrypoint), WarningType::MissingMain)
+        let similar = self.book.similar_def_name(&entrypoint);
+        self.info.add_book_warning(EntryErr::NotFound(entrypoint, similar), WarningType::MissingMain)
       }
     }
 
@@ -67,12 +69,50 @@ impl Book {
     let hvm1_main = self.defs.get(&Name::new(HVM1_ENTRY_POINT));
     (custom, main, hvm1_main)
   }
+
+  /// Returns the user definition whose name is closest to `name`, if it's close enough to be a typo.
+  fn similar_def_name(&self, name: &Name) -> Option<Name> {
+    let max_dist = 1 + name.len() / 3;
+    self
+      .defs
+      .values()
+      .filter(|def| def.source.is_local())
+      .map(|def| (edit_distance(name, &def.name), &def.name))
+      .filter(|(dist, _)| *dist <= max_dist)
+      .min_by_key(|(dist, _)| *dist)
+      .map(|(_, name)| name.clone())
+  }
+}
+
+/// Levenshtein distance between two strings, counting transpositions of adjacent characters as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let a = a.chars().collect::<Vec<_>>();
+  let b = b.chars().collect::<Vec<_>>();
+  let mut dist = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for (i, row) in dist.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  dist[0] = (0..=b.len()).collect();
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      let mut d = (dist[i - 1][j] + 1).min(dist[i][j - 1] + 1).min(dist[i - 1][j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        d = d.min(dist[i - 2][j - 2] + 1);
+      }
+      dist[i][j] = d;
+    }
+  }
+  dist[a.len()][b.len()]
 }
 
 impl std::fmt::Display for EntryErr {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
-      EntryErr::NotFound(name) => write!(f, "File has no '{name}' definition."),
+      EntryErr::NotFound(name, None) => write!(f, "File has no '{name}' definition."),
+      EntryErr::NotFound(name, Some(similar)) => {
+        write!(f, "File has no '{name}' definition. Did you mean '{similar}'?")
+      }
       EntryErr::Multiple(fnd) if fnd.len() == 2 => {
         write!(f, "File has both '{}' and '{}' definitions.", fnd[0], fnd[1])
       }
@@ -549,6 +549,7 @@ fn infer(
     | Term::Bend { .. }
     | Term::Open { .. }
     | Term::Def { .. }
+    | Term::Hole { .. }
     | Term::Err => unreachable!("'{term}' while type checking. Should have been removed in earlier pass"),
   })?;
   Ok(res)
@@ -156,6 +156,7 @@ impl fmt::Display for Term {
       }
       Term::Fan { fan: FanKind::Tup, tag, els } => write!(f, "{}({})", tag, DisplayJoin(|| els.iter(), ", ")),
       Term::Fan { fan: FanKind::Dup, tag, els } => write!(f, "{}{{{}}}", tag, DisplayJoin(|| els, " ")),
+      Term::Hole { nam } => write!(f, "?{nam}"),
       Term::Era => write!(f, "*"),
       Term::Num { val: Num::U24(val) } => write!(f, "{val}"),
       Term::Num { val: Num::I24(val) } => write!(f, "{}{}", if *val < 0 { "-" } else { "+" }, val.abs()),
@@ -548,6 +549,7 @@ impl Term {
           }
           write!(f, "{:tab$}{}", "", nxt.display_pretty(tab))
         }
+        Term::Hole { nam } => write!(f, "?{nam}"),
         Term::Era => write!(f, "*"),
         Term::Err => write!(f, "<Error>"),
       })
@@ -39,14 +39,26 @@ pub fn load_to_book(
   book.load_imports(package_loader, diag)
 }
 
-pub fn do_parse_book(code: &str, origin: &Path, mut book: ParseBook) -> Result<ParseBook, Diagnostics> {
+pub fn do_parse_book(code: &str, origin: &Path, book: ParseBook) -> Result<ParseBook, Diagnostics> {
+  let (book, diagnostics) = parse_book_lenient(code, origin, book);
+  if diagnostics.has_errors() {
+    Err(diagnostics)
+  } else {
+    Ok(book)
+  }
+}
+
+/// Parses a book, skipping over the top-level items that have syntax errors.
+/// Returns the successfully parsed items together with the errors of the skipped ones.
+pub fn parse_book_lenient(code: &str, origin: &Path, mut book: ParseBook) -> (ParseBook, Diagnostics) {
   book.source = Name::new(origin.to_string_lossy());
-  FunParser::new(book.source.clone(), code, false).parse_book(book).map_err(|err| {
-    let mut diagnostics = Diagnostics::default();
+  let (book, errs) = FunParser::new(book.source.clone(), code, false).parse_book_lenient(book);
+  let mut diagnostics = Diagnostics::default();
+  for err in errs {
     let span = TextSpan::from_byte_span(code, err.span.0..err.span.1);
     let source =
       Source { file: Some(origin.to_string_lossy().into()), span: Some(span), kind: SourceKind::User };
     diagnostics.add_parsing_error(err, source);
-    diagnostics
-  })
+  }
+  (book, diagnostics)
 }
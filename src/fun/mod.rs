@@ -18,6 +18,8 @@ pub mod display;
 pub mod load_book;
 pub mod net_to_term;
 pub mod parser;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod term_to_net;
 pub mod transform;
 
@@ -32,13 +34,14 @@ pub struct Ctx<'book> {
 }
 
 impl Ctx<'_> {
-  pub fn new(book: &mut Book, diagnostics_cfg: DiagnosticsConfig) -> Ctx {
+  pub fn new(book: &mut Book, diagnostics_cfg: DiagnosticsConfig) -> Ctx<'_> {
     Ctx { book, info: Diagnostics::new(diagnostics_cfg) }
   }
 }
 
 /// The representation of a program.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Book {
   /// Function definitions.
   pub defs: Definitions,
@@ -66,6 +69,7 @@ pub type Constructors = IndexMap<Name, Name>;
 
 /// A pattern matching function definition.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Definition {
   pub name: Name,
   pub typ: Type,
@@ -75,6 +79,7 @@ pub struct Definition {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Source {
   pub file: Option<String>,
   pub span: Option<TextSpan>,
@@ -82,6 +87,7 @@ pub struct Source {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SourceKind {
   /// Built into the language.
   Builtin,
@@ -97,14 +103,17 @@ pub enum SourceKind {
 
 /// An HVM native definition.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HvmDefinition {
   pub name: Name,
   pub typ: Type,
+  #[cfg_attr(feature = "serde", serde(with = "serialize::hvm_net"))]
   pub body: hvm::ast::Net,
   pub source: Source,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
   Any,
   Hole,
@@ -122,12 +131,14 @@ pub enum Type {
 
 /// A pattern matching rule of a definition.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rule {
   pub pats: Vec<Pattern>,
   pub body: Term,
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Term {
   Lam {
     tag: Tag,
@@ -177,6 +188,7 @@ pub enum Term {
     val: u32,
   },
   Str {
+    #[cfg_attr(feature = "serde", serde(with = "serialize::global_string"))]
     val: GlobalString,
   },
   List {
@@ -231,6 +243,10 @@ pub enum Term {
     def: Definition,
     nxt: Box<Term>,
   },
+  /// A `?name` placeholder for a term that wasn't written yet.
+  Hole {
+    nam: Name,
+  },
   Era,
   #[default]
   Err,
@@ -239,12 +255,14 @@ pub enum Term {
 pub type MatchRule = (Option<Name>, Vec<Option<Name>>, Term);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FanKind {
   Tup,
   Dup,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op {
   ADD,
   SUB,
@@ -269,6 +287,7 @@ pub enum Op {
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Num {
   U24(u32),
   I24(i32),
@@ -276,6 +295,7 @@ pub enum Num {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pattern {
   Var(Option<Name>),
   Chn(Name),
@@ -284,10 +304,11 @@ pub enum Pattern {
   /// Either a tuple or a duplication
   Fan(FanKind, Tag, Vec<Pattern>),
   Lst(Vec<Pattern>),
-  Str(GlobalString),
+  Str(#[cfg_attr(feature = "serde", serde(with = "serialize::global_string"))] GlobalString),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tag {
   Named(Name),
   Numeric(u16),
@@ -298,6 +319,7 @@ pub enum Tag {
 
 /// A user defined datatype
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Adt {
   pub name: Name,
   pub vars: Vec<Name>,
@@ -306,6 +328,7 @@ pub struct Adt {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdtCtr {
   pub name: Name,
   pub typ: Type,
@@ -313,6 +336,7 @@ pub struct AdtCtr {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CtrField {
   pub nam: Name,
   pub rec: bool,
@@ -393,6 +417,7 @@ impl Clone for Term {
       Self::Lam { tag, pat, bod } => Self::Lam { tag: tag.clone(), pat: pat.clone(), bod: bod.clone() },
       Self::Var { nam } => Self::Var { nam: nam.clone() },
       Self::Link { nam } => Self::Link { nam: nam.clone() },
+      Self::Hole { nam } => Self::Hole { nam: nam.clone() },
       Self::Let { pat, val, nxt } => Self::Let { pat: pat.clone(), val: val.clone(), nxt: nxt.clone() },
       Self::With { typ, bod } => Self::With { typ: typ.clone(), bod: bod.clone() },
       Self::Ask { pat, val, nxt } => Self::Ask { pat: pat.clone(), val: val.clone(), nxt: nxt.clone() },
@@ -606,6 +631,7 @@ impl Term {
       | Term::Str { .. }
       | Term::Ref { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => ChildrenIter::Zero([]),
     }
@@ -642,6 +668,7 @@ impl Term {
       | Term::Str { .. }
       | Term::Ref { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => ChildrenIter::Zero([]),
     }
@@ -710,6 +737,7 @@ impl Term {
       | Term::Str { .. }
       | Term::Ref { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => ChildrenIter::Zero([]),
       Term::Open { .. } => unreachable!("Open should be removed in earlier pass"),
@@ -774,6 +802,7 @@ impl Term {
       | Term::Str { .. }
       | Term::Ref { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => ChildrenIter::Zero([]),
       Term::Open { .. } => unreachable!("Open should be removed in earlier pass"),
@@ -825,10 +854,8 @@ impl Term {
           }
         }
       }
-      Term::Open { typ, .. } => {
-        if typ == from {
-          *typ = to.clone();
-        }
+      Term::Open { typ, .. } if typ == from => {
+        *typ = to.clone();
       }
       _ => (),
     }
@@ -841,10 +868,8 @@ impl Term {
         Term::Def { def, nxt: _ } => {
           def.typ.subst_ctr(from, to);
         }
-        Term::With { typ, bod: _ } => {
-          if typ == from {
-            *typ = to.clone();
-          }
+        Term::With { typ, bod: _ } if typ == from => {
+          *typ = to.clone();
         }
         _ => (),
       }
@@ -950,6 +975,23 @@ impl Term {
   }
 }
 
+impl Op {
+  /// Operations that compare their operands, returning 1 if the comparison holds and 0 otherwise.
+  pub fn is_comparison(&self) -> bool {
+    matches!(self, Op::EQ | Op::NEQ | Op::LT | Op::GT | Op::LE | Op::GE)
+  }
+
+  /// Operations that act on the bits of integer operands.
+  pub fn is_bitwise(&self) -> bool {
+    matches!(self, Op::AND | Op::OR | Op::XOR | Op::SHL | Op::SHR)
+  }
+
+  /// Operations that return the result of an arithmetic calculation.
+  pub fn is_arithmetic(&self) -> bool {
+    matches!(self, Op::ADD | Op::SUB | Op::MUL | Op::DIV | Op::REM | Op::POW)
+  }
+}
+
 impl Num {
   pub fn is_zero(&self) -> bool {
     match self {
@@ -1126,15 +1168,8 @@ impl Type {
   pub fn subst_ctr(&mut self, from: &Name, to: &Name) {
     maybe_grow(|| {
       match self {
-        Type::Var(nam) => {
-          if nam == from {
-            *nam = to.clone();
-          }
-        }
-        Type::Ctr(nam, _) => {
-          if nam == from {
-            *nam = to.clone();
-          }
+        Type::Var(nam) | Type::Ctr(nam, _) if nam == from => {
+          *nam = to.clone();
         }
         _ => (),
       };
@@ -1285,3 +1320,37 @@ fn num_to_from_bits() {
     assert_eq!(b, Num::from_bits(Num::to_bits(&b)));
   }
 }
+
+#[test]
+fn op_classes() {
+  let ops = [
+    Op::ADD,
+    Op::SUB,
+    Op::MUL,
+    Op::DIV,
+    Op::REM,
+    Op::EQ,
+    Op::NEQ,
+    Op::LT,
+    Op::GT,
+    Op::AND,
+    Op::OR,
+    Op::XOR,
+    Op::SHL,
+    Op::SHR,
+    Op::POW,
+    Op::LE,
+    Op::GE,
+  ];
+  for op in ops {
+    // Exhaustive, so that a new operation has to be classified here (and added to `ops`).
+    let expected = match op {
+      Op::ADD | Op::SUB | Op::MUL | Op::DIV | Op::REM | Op::POW => (true, false, false),
+      Op::EQ | Op::NEQ | Op::LT | Op::GT | Op::LE | Op::GE => (false, true, false),
+      Op::AND | Op::OR | Op::XOR | Op::SHL | Op::SHR => (false, false, true),
+    };
+    let classes = (op.is_arithmetic(), op.is_comparison(), op.is_bitwise());
+    assert_eq!(classes, expected, "{op:?}");
+    assert_eq!([classes.0, classes.1, classes.2].into_iter().filter(|c| *c).count(), 1, "{op:?}");
+  }
+}
@@ -16,6 +16,14 @@ use TSPL::{ParseError, Parser};
 type FunDefinition = super::Definition;
 type ImpDefinition = crate::imp::Definition;
 
+const U24_MAX: i64 = 0xFFFFFF;
+const I24_MIN: i64 = -0x800000;
+const I24_MAX: i64 = 0x7FFFFF;
+
+/// Keywords that start a functional term, so they can't be used as variable names.
+const RESERVED_KEYWORDS: &[&str] =
+  &["ask", "bend", "def", "fold", "if", "let", "match", "open", "switch", "use", "with"];
+
 /// Intermediate representation of a program.
 #[derive(Debug, Clone, Default)]
 pub struct ParseBook {
@@ -54,6 +62,21 @@ impl ParseBook {
       .or_else(|| self.imp_defs.get(name).map(|d| d.source.is_builtin()))
       .or_else(|| self.hvm_defs.get(name).map(|d| d.source.is_builtin()))
   }
+
+  /// Returns the source of the definition with the given name, if it exists.
+  pub fn def_source(&self, name: &Name) -> Option<&Source> {
+    self
+      .fun_defs
+      .get(name)
+      .map(|d| &d.source)
+      .or_else(|| self.imp_defs.get(name).map(|d| &d.source))
+      .or_else(|| self.hvm_defs.get(name).map(|d| &d.source))
+  }
+
+  /// Returns the source of the type that declares the given constructor, if it exists.
+  pub fn ctr_source(&self, name: &Name) -> Option<&Source> {
+    self.ctrs.get(name).and_then(|adt| self.adts.get(adt)).map(|adt| &adt.source)
+  }
 }
 
 pub type ParseResult<T> = std::result::Result<T, ParseError>;
@@ -63,130 +86,191 @@ pub struct FunParser<'i> {
   input: &'i str,
   index: usize,
   builtin: bool,
+  /// Counter for the fresh variables bound by operator sections.
+  fresh: usize,
 }
 
 impl<'a> FunParser<'a> {
   pub fn new(file: Name, input: &'a str, builtin: bool) -> Self {
-    Self { file, input, index: 0, builtin }
+    Self { file, input, index: 0, builtin, fresh: 0 }
   }
 
   /* AST parsing functions */
 
   pub fn parse_book(&mut self, default_book: ParseBook) -> ParseResult<ParseBook> {
     let mut book = default_book;
+    self.skip_bom();
     let mut indent = self.advance_newlines()?;
     while !self.is_eof() {
-      // Record type definition
-      if self.starts_with_keyword("object") {
-        let ini_idx = *self.index();
+      indent = self.parse_top_level(&mut book, indent)?;
+    }
+    Ok(book)
+  }
+
+  /// Parses a book like `parse_book`, but instead of stopping at the first error,
+  /// skips to the next top-level definition and continues parsing.
+  /// Returns the book with every item that parsed successfully,
+  /// together with all the errors found, in the order they appear in the file.
+  pub fn parse_book_lenient(&mut self, default_book: ParseBook) -> (ParseBook, Vec<ParseError>) {
+    let mut book = default_book;
+    let mut errs = vec![];
+    self.skip_bom();
+    let mut indent = match self.advance_newlines() {
+      Ok(indent) => indent,
+      Err(err) => return (book, vec![err]),
+    };
+    while !self.is_eof() {
+      let ini_idx = *self.index();
+      match self.parse_top_level(&mut book, indent) {
+        Ok(nxt_indent) => indent = nxt_indent,
+        Err(err) => {
+          errs.push(err);
+          if *self.index() <= ini_idx {
+            *self.index() = ini_idx;
+            self.advance_one();
+          }
+          self.skip_to_next_top_level();
+          indent = Indent::Val(0);
+        }
+      }
+    }
+    (book, errs)
+  }
+
+  /// Skips the UTF-8 byte order mark that some editors add to the start of files.
+  fn skip_bom(&mut self) {
+    if *self.index() == 0 && self.starts_with("\u{feff}") {
+      self.advance_one();
+    }
+  }
+
+  /// Parses one top-level item, adding it to the book.
+  /// Returns the indentation of the line after it.
+  fn parse_top_level(&mut self, book: &mut ParseBook, indent: Indent) -> ParseResult<Indent> {
+    // Record type definition
+    if self.starts_with_keyword("object") {
+      let ini_idx = *self.index();
+      let mut prs =
+        ImpParser { file: self.file.clone(), input: self.input, index: *self.index(), builtin: self.builtin };
+      let (adt, nxt_indent) = prs.parse_object(indent)?;
+      self.index = prs.index;
+      let end_idx = *self.index();
+      self.add_type_def(adt, book, ini_idx..end_idx)?;
+      return Ok(nxt_indent);
+    }
+
+    // Imp function definition
+    if self.starts_with_keyword("def") {
+      let ini_idx = *self.index();
+      let mut prs =
+        ImpParser { file: self.file.clone(), input: self.input, index: ini_idx, builtin: self.builtin };
+      let (def, nxt_indent) = prs.parse_function_def(indent)?;
+      self.index = prs.index;
+      let end_idx = *self.index();
+      self.add_imp_def(def, book, ini_idx..end_idx)?;
+      return Ok(nxt_indent);
+    }
+
+    // Fun/Imp type definition
+    if self.starts_with_keyword("type") {
+      fn starts_with_imp_type(p: &mut FunParser) -> ParseResult<()> {
+        p.parse_keyword("type")?;
+        p.skip_trivia_inline()?;
+        p.parse_top_level_name()?;
+        p.skip_trivia_inline()?;
+        if p.starts_with(":") || p.starts_with("(") {
+          Ok(())
+        } else {
+          Err(ParseError::new((0, 0), ""))
+        }
+      }
+
+      let ini_idx = *self.index();
+      let is_imp = starts_with_imp_type(self).is_ok();
+      self.index = ini_idx;
+      if is_imp {
+        // Imp type definition
         let mut prs = ImpParser {
           file: self.file.clone(),
           input: self.input,
           index: *self.index(),
           builtin: self.builtin,
         };
-        let (adt, nxt_indent) = prs.parse_object(indent)?;
+        let (adt, nxt_indent) = prs.parse_type_def(indent)?;
         self.index = prs.index;
         let end_idx = *self.index();
-        self.add_type_def(adt, &mut book, ini_idx..end_idx)?;
-        indent = nxt_indent;
-        continue;
-      }
-
-      // Imp function definition
-      if self.starts_with_keyword("def") {
-        let ini_idx = *self.index();
-        let mut prs =
-          ImpParser { file: self.file.clone(), input: self.input, index: ini_idx, builtin: self.builtin };
-        let (def, nxt_indent) = prs.parse_function_def(indent)?;
-        self.index = prs.index;
+        self.add_type_def(adt, book, ini_idx..end_idx)?;
+        return Ok(nxt_indent);
+      } else {
+        // Fun type definition
+        let adt = self.parse_type_def()?;
         let end_idx = *self.index();
-        self.add_imp_def(def, &mut book, ini_idx..end_idx)?;
-        indent = nxt_indent;
-        continue;
+        self.add_type_def(adt, book, ini_idx..end_idx)?;
+        return self.advance_newlines();
       }
+    }
 
-      // Fun/Imp type definition
-      if self.starts_with_keyword("type") {
-        fn starts_with_imp_type(p: &mut FunParser) -> ParseResult<()> {
-          p.parse_keyword("type")?;
-          p.skip_trivia_inline()?;
-          p.parse_top_level_name()?;
-          p.skip_trivia_inline()?;
-          if p.starts_with(":") || p.starts_with("(") {
-            Ok(())
-          } else {
-            Err(ParseError::new((0, 0), ""))
-          }
-        }
+    // HVM native function definition
+    if self.starts_with_keyword("hvm") {
+      let ini_idx = self.index;
+      let mut prs =
+        ImpParser { file: self.file.clone(), input: self.input, index: self.index, builtin: self.builtin };
+      let (def, nxt_indent) = prs.parse_hvm()?;
+      *self.index() = prs.index;
+      let end_idx = *self.index();
+      self.add_hvm(def, book, ini_idx..end_idx)?;
+      return Ok(nxt_indent);
+    }
 
-        let ini_idx = *self.index();
-        let is_imp = starts_with_imp_type(self).is_ok();
-        self.index = ini_idx;
-        if is_imp {
-          // Imp type definition
-          let mut prs = ImpParser {
-            file: self.file.clone(),
-            input: self.input,
-            index: *self.index(),
-            builtin: self.builtin,
-          };
-          let (adt, nxt_indent) = prs.parse_type_def(indent)?;
-          self.index = prs.index;
-          let end_idx = *self.index();
-          self.add_type_def(adt, &mut book, ini_idx..end_idx)?;
-          indent = nxt_indent;
-          continue;
-        } else {
-          // Fun type definition
-          let adt = self.parse_type_def()?;
-          let end_idx = *self.index();
-          self.add_type_def(adt, &mut book, ini_idx..end_idx)?;
-          indent = self.advance_newlines()?;
-          continue;
-        }
-      }
+    // Import declaration
+    if self.starts_with_keyword("from") {
+      let import = self.parse_from_import()?;
+      book.import_ctx.add_import(import);
+      return self.advance_newlines();
+    }
 
-      // HVM native function definition
-      if self.starts_with_keyword("hvm") {
-        let ini_idx = self.index;
-        let mut prs =
-          ImpParser { file: self.file.clone(), input: self.input, index: self.index, builtin: self.builtin };
-        let (def, nxt_indent) = prs.parse_hvm()?;
-        *self.index() = prs.index;
-        let end_idx = *self.index();
-        self.add_hvm(def, &mut book, ini_idx..end_idx)?;
-        indent = nxt_indent;
-        continue;
+    if self.starts_with_keyword("import") {
+      let imports = self.parse_import()?;
+      for imp in imports {
+        book.import_ctx.add_import(imp);
       }
+      return self.advance_newlines();
+    }
 
-      // Import declaration
-      if self.starts_with_keyword("from") {
-        let import = self.parse_from_import()?;
-        book.import_ctx.add_import(import);
-        indent = self.advance_newlines()?;
-        continue;
+    // Fun function definition
+    let ini_idx = *self.index();
+    let def = self.parse_fun_def()?;
+    let end_idx = *self.index();
+
+    self.add_fun_def(def, book, ini_idx..end_idx)?;
+
+    // Rules can also be separated by `;` to write several of them in the same line.
+    self.advance_trivia_inline()?;
+    if self.try_consume_exactly(";") {
+      self.advance_trivia_inline()?;
+      if !self.is_eof() && !self.starts_with("\n") && !self.starts_with("\r") {
+        return Ok(Indent::Val(0));
       }
+    }
+    self.advance_newlines()
+  }
 
-      if self.starts_with_keyword("import") {
-        let imports = self.parse_import()?;
-        for imp in imports {
-          book.import_ctx.add_import(imp);
+  /// Skips to the next line that starts with something other than trivia or a closing delimiter.
+  /// Since top-level definitions are not indented, this is where the next one begins.
+  fn skip_to_next_top_level(&mut self) {
+    while !self.is_eof() {
+      if self.starts_with("#{") {
+        if self.advance_multi_line_comment().is_err() {
+          self.index = self.input.len();
         }
-        indent = self.advance_newlines()?;
         continue;
       }
-
-      // Fun function definition
-      let ini_idx = *self.index();
-      let def = self.parse_fun_def()?;
-      let end_idx = *self.index();
-
-      self.add_fun_def(def, &mut book, ini_idx..end_idx)?;
-      indent = self.advance_newlines()?;
+      let at_line_start = self.index == 0 || self.input[..self.index].ends_with('\n');
+      if at_line_start && self.peek_one().is_some_and(|c| !c.is_ascii_whitespace() && !"#)]}".contains(c)) {
+        return;
+      }
+      self.advance_one();
     }
-
-    Ok(book)
   }
 
   fn parse_type_def(&mut self) -> ParseResult<Adt> {
@@ -292,7 +376,7 @@ impl<'a> FunParser<'a> {
         let mut rules = vec![];
         let (_, rule) = self.parse_rule()?;
         rules.push(rule);
-        while self.starts_with_rule(&name) {
+        while self.continues_with_rule(&name) {
           let (_, rule) = self.parse_rule()?;
           rules.push(rule);
         }
@@ -309,7 +393,7 @@ impl<'a> FunParser<'a> {
       let mut rules = vec![];
       let (name, rule) = self.parse_rule()?;
       rules.push(rule);
-      while self.starts_with_rule(&name) {
+      while self.continues_with_rule(&name) {
         let (_, rule) = self.parse_rule()?;
         rules.push(rule);
       }
@@ -456,6 +540,20 @@ impl<'a> FunParser<'a> {
     Ok((name, rule))
   }
 
+  /// Checks if the next item is another rule of the same function,
+  /// consuming the `;` that may separate it from the previous one.
+  fn continues_with_rule(&mut self, expected_name: &Name) -> bool {
+    let ini_idx = *self.index();
+    self.skip_trivia();
+    self.try_consume_exactly(";");
+    if self.starts_with_rule(expected_name) {
+      true
+    } else {
+      self.index = ini_idx;
+      false
+    }
+  }
+
   fn starts_with_rule(&mut self, expected_name: &Name) -> bool {
     let ini_idx = *self.index();
     self.skip_trivia();
@@ -537,9 +635,13 @@ impl<'a> FunParser<'a> {
       }
 
       // Number
-      if self.peek_one().map_or(false, |c| c.is_ascii_digit()) {
+      if self.peek_one().is_some_and(|c| c.is_ascii_digit()) {
         unexpected_tag(self)?;
+        let ini_idx = *self.index();
         let num = self.parse_u32()?;
+        if num as i64 > U24_MAX {
+          return self.num_range_err(ini_idx, "U24", 0, U24_MAX);
+        }
         return Ok(Pattern::Num(num));
       }
 
@@ -560,6 +662,8 @@ impl<'a> FunParser<'a> {
       {
         unexpected_tag(self)?;
         let nam = self.parse_name_or_era()?;
+        // `_` is a wildcard, it binds nothing just like `*`.
+        let nam = nam.filter(|nam| nam != "_");
         return Ok(Pattern::Var(nam));
       }
 
@@ -573,6 +677,19 @@ impl<'a> FunParser<'a> {
     })
   }
 
+  /// Parses an input that must contain a single term, like a program argument.
+  /// Surrounding whitespace and comments are allowed, but anything else after the term is an error.
+  pub fn parse_standalone_term(&mut self) -> ParseResult<Term> {
+    let term = self.parse_term()?;
+    self.skip_trivia();
+    if !self.is_eof() {
+      let ini_idx = *self.index();
+      let end_idx = self.input.len();
+      return self.err_msg_spanned("Unexpected input after the end of the term.", ini_idx..end_idx);
+    }
+    Ok(term)
+  }
+
   pub fn parse_term(&mut self) -> ParseResult<Term> {
     maybe_grow(|| {
       let (tag, unexpected_tag) = self.parse_tag()?;
@@ -593,7 +710,11 @@ impl<'a> FunParser<'a> {
 
         // Opr but maybe a tup
         self.skip_trivia();
-        if let Some(opr) = self.try_parse_oper() {
+        // A signed number directly followed by `)` or `,`, like `(-3)` or `(-3, 4)`, is just the number.
+        // Otherwise the sign is an operator, so `(-1 2)` is still `(- 1 2)`.
+        let signed_num = self.try_parse_signed_num_elem()?;
+        let opr = if signed_num.is_some() { None } else { self.try_parse_oper() };
+        if let Some(opr) = opr {
           self.skip_trivia();
 
           // jk, actually a tuple
@@ -610,14 +731,36 @@ impl<'a> FunParser<'a> {
 
           // Opr
           unexpected_tag(self)?;
+
+          // Operator section without operands, `(+)` is `λ%x0 λ%x1 (+ %x0 %x1)`
+          if self.try_consume(")") {
+            let x = self.fresh_var();
+            let y = self.fresh_var();
+            let fst = Box::new(Term::Var { nam: x.clone() });
+            let snd = Box::new(Term::Var { nam: y.clone() });
+            let bod = Term::Oper { opr, fst, snd };
+            return Ok(Term::rfold_lams(bod, [Some(x), Some(y)].into_iter()));
+          }
+
           let fst = self.parse_term()?;
+
+          // Operator section with one operand, `(+ 1)` is `λ%x0 (+ 1 %x0)`
+          if self.try_consume(")") {
+            let y = self.fresh_var();
+            let bod = Term::Oper { opr, fst: Box::new(fst), snd: Box::new(Term::Var { nam: y.clone() }) };
+            return Ok(Term::lam(Pattern::Var(Some(y)), bod));
+          }
+
           let snd = self.parse_term()?;
           self.consume(")")?;
           return Ok(Term::Oper { opr, fst: Box::new(fst), snd: Box::new(snd) });
         }
 
         // Tup or App
-        let head = self.parse_term()?;
+        let head = match signed_num {
+          Some(num) => num,
+          None => self.parse_term()?,
+        };
 
         // Tup
         self.skip_trivia();
@@ -689,6 +832,14 @@ impl<'a> FunParser<'a> {
         return Ok(Term::Era);
       }
 
+      // Hole
+      if self.starts_with("?") {
+        self.advance_one();
+        unexpected_tag(self)?;
+        let nam = self.parse_var_name()?;
+        return Ok(Term::Hole { nam });
+      }
+
       // Nat
       if self.starts_with("#") {
         self.advance_one();
@@ -719,7 +870,7 @@ impl<'a> FunParser<'a> {
       }
 
       // Native Number
-      if self.peek_one().map_or(false, is_num_char) {
+      if self.peek_one().is_some_and(is_num_char) {
         unexpected_tag(self)?;
         let num = self.parse_number()?;
         return Ok(Term::Num { val: num });
@@ -729,7 +880,7 @@ impl<'a> FunParser<'a> {
       if self.try_parse_keyword("use") {
         unexpected_tag(self)?;
         self.skip_trivia();
-        let nam = self.parse_var_name()?;
+        let nam = self.parse_term_var_name()?;
         self.consume("=")?;
         let val = self.parse_term()?;
         self.try_consume(";");
@@ -874,7 +1025,7 @@ impl<'a> FunParser<'a> {
         unexpected_tag(self)?;
         let args = self.list_like(
           |p| {
-            let bind = p.parse_var_name()?;
+            let bind = p.parse_term_var_name()?;
             let init = if p.try_consume("=") { p.parse_term()? } else { Term::Var { nam: bind.clone() } };
             Ok((bind, init))
           },
@@ -911,7 +1062,7 @@ impl<'a> FunParser<'a> {
         self.skip_trivia();
         let typ = self.parse_top_level_name()?;
         self.skip_trivia();
-        let var = self.parse_var_name()?;
+        let var = self.parse_term_var_name()?;
         self.try_consume(";");
         let bod = self.parse_term()?;
         return Ok(Term::Open { typ, var, bod: Box::new(bod) });
@@ -919,23 +1070,40 @@ impl<'a> FunParser<'a> {
 
       // Var
       unexpected_tag(self)?;
-      let nam = self.labelled(|p| p.parse_var_name(), "term")?;
+      let ini_idx = *self.index();
+      let nam = self.labelled(|p| p.parse_term_var_name(), "term")?;
+      // A `_:` is a misplaced match arm, not a use of `_`, so leave it to be reported as such.
+      if nam == "_" && !self.starts_with(":") {
+        let end_idx = *self.index();
+        return self
+          .err_msg_spanned("'_' is a wildcard pattern and can't be used as a variable.", ini_idx..end_idx);
+      }
       Ok(Term::Var { nam })
     })
   }
 
+  /// Parses the name of a variable of a term, which can't be one of the keywords that start a term.
+  fn parse_term_var_name(&mut self) -> ParseResult<Name> {
+    let ini_idx = *self.index();
+    let name = self.parse_var_name()?;
+    if RESERVED_KEYWORDS.contains(&name.as_ref()) {
+      let end_idx = *self.index();
+      let msg = format!("'{name}' is a reserved keyword and can't be used as a variable name.");
+      return self.err_msg_spanned(&msg, ini_idx..end_idx);
+    }
+    Ok(name)
+  }
+
   fn parse_name_or_era(&mut self) -> ParseResult<Option<Name>> {
-    self.labelled(
-      |p| {
-        if p.try_consume_exactly("*") {
-          Ok(None)
-        } else {
-          let nam = p.parse_var_name()?;
-          Ok(Some(nam))
-        }
-      },
-      "name or '*'",
-    )
+    if self.try_consume_exactly("*") {
+      Ok(None)
+    } else if self.peek_one().is_some_and(is_name_char) {
+      // Report the specific error for invalid names, like reserved keywords.
+      let nam = self.parse_term_var_name()?;
+      Ok(Some(nam))
+    } else {
+      self.expected("name or '*'")
+    }
   }
 
   /// Parses a tag where it may or may not be valid.
@@ -963,8 +1131,21 @@ impl<'a> FunParser<'a> {
     }))
   }
 
+  /// Generates a new variable name, distinct from the other generated ones and from user variables.
+  fn fresh_var(&mut self) -> Name {
+    let nam = Name::new(format!("%x{}", self.fresh));
+    self.fresh += 1;
+    nam
+  }
+
   // A named arg with optional name.
   fn parse_match_arg(&mut self) -> ParseResult<(Option<Name>, Term)> {
+    // `_ = arg` is allowed even though `_` can't be used as a term.
+    self.skip_trivia();
+    if self.try_parse_keyword("_") {
+      self.consume("=")?;
+      return Ok((Some(Name::new("_")), self.parse_term()?));
+    }
     let ini_idx = *self.index();
     let mut arg = self.parse_term()?;
     let end_idx = *self.index();
@@ -983,7 +1164,7 @@ impl<'a> FunParser<'a> {
 
   /// A named arg with non-optional name.
   fn parse_named_arg(&mut self) -> ParseResult<(Option<Name>, Term)> {
-    let nam = self.parse_var_name()?;
+    let nam = self.parse_term_var_name()?;
     self.skip_trivia();
     if self.starts_with("=") {
       self.advance_one();
@@ -1112,16 +1293,19 @@ impl<'a> FunParser<'a> {
     for ctr in adt.ctrs.keys() {
       if let Some(builtin) = book.contains_builtin_def(ctr) {
         let msg = FunParser::redefinition_of_function_msg(builtin, ctr);
+        let msg = self.with_first_definition_msg(msg, book.def_source(ctr));
         return self.err_msg_spanned(&msg, span);
       }
-      match book.ctrs.entry(ctr.clone()) {
-        indexmap::map::Entry::Vacant(e) => _ = e.insert(adt.name.clone()),
-        indexmap::map::Entry::Occupied(e) => {
-          let msg = FunParser::redefinition_of_constructor_msg(e.key());
-          return self.err_msg_spanned(&msg, span);
-        }
+      if book.ctrs.contains_key(ctr) {
+        let msg = FunParser::redefinition_of_constructor_msg(ctr);
+        let msg = self.with_first_definition_msg(msg, book.ctr_source(ctr));
+        return self.err_msg_spanned(&msg, span);
       }
     }
+    // Only add the constructors once all of them are valid, so that a rejected type leaves none behind.
+    for ctr in adt.ctrs.keys() {
+      book.ctrs.insert(ctr.clone(), adt.name.clone());
+    }
     book.adts.insert(adt.name.clone(), adt);
     Ok(())
   }
@@ -1134,14 +1318,17 @@ impl<'a> FunParser<'a> {
   ) -> ParseResult<()> {
     if let Some(builtin) = book.contains_builtin_def(name) {
       let msg = Self::redefinition_of_function_msg(builtin, name);
+      let msg = self.with_first_definition_msg(msg, book.def_source(name));
       return self.err_msg_spanned(&msg, span);
     }
     if book.ctrs.contains_key(name) {
       let msg = Self::redefinition_of_constructor_msg(name);
+      let msg = self.with_first_definition_msg(msg, book.ctr_source(name));
       return self.err_msg_spanned(&msg, span);
     }
-    if book.hvm_defs.contains_key(name) {
+    if let Some(hvm_def) = book.hvm_defs.get(name) {
       let msg = Self::redefinition_of_hvm_msg(false, name);
+      let msg = self.with_first_definition_msg(msg, Some(&hvm_def.source));
       return self.err_msg_spanned(&msg, span);
     }
     Ok(())
@@ -1153,12 +1340,23 @@ impl<'a> FunParser<'a> {
     book: &mut ParseBook,
     span: Range<usize>,
   ) -> ParseResult<()> {
-    if book.adts.contains_key(name) {
+    if let Some(adt) = book.adts.get(name) {
       let msg = Self::redefinition_of_type_msg(name);
+      let msg = self.with_first_definition_msg(msg, Some(&adt.source));
       return self.err_msg_spanned(&msg, span);
     }
     Ok(())
   }
+
+  /// Adds where a name was first defined to a redefinition error, if it was in the same file.
+  fn with_first_definition_msg(&self, msg: String, source: Option<&Source>) -> String {
+    match source {
+      Some(Source { file: Some(file), span: Some(span), .. }) if *file == *self.file => {
+        format!("{msg}\nFirst defined at line {}.", span.start.line + 1)
+      }
+      _ => msg,
+    }
+  }
 }
 
 impl<'a> Parser<'a> for FunParser<'a> {
@@ -1188,6 +1386,13 @@ impl<'a> Parser<'a> for FunParser<'a> {
     self.expected_spanned_and(exp, msg, ini_idx..end_idx)
   }
 
+  /// Parses a single unicode character, supporting escape sequences.
+  ///
+  /// Override to have our own error messages.
+  fn parse_char(&mut self) -> ParseResult<char> {
+    self.parse_char_or_escape()
+  }
+
   /// Consumes an instance of the given string, erroring if it is not found.
   ///
   /// Override to have our own error message.
@@ -1207,30 +1412,19 @@ impl<'a> Parser<'a> for FunParser<'a> {
         self.advance_one();
         continue;
       }
+      if self.starts_with("#{") {
+        // Unterminated comments are left in place, to be reported by the next error (see `with_ctx`).
+        if self.advance_multi_line_comment().is_err() {
+          break;
+        }
+        continue;
+      }
       if c == '#' {
-        self.advance_one();
-        if let Some(c) = self.peek_one() {
-          if c == '{' {
+        while let Some(c) = self.peek_one() {
+          if c != '\n' {
             self.advance_one();
-            while let Some(c) = self.peek_one() {
-              self.advance_one();
-              if c == '#' {
-                if let Some('}') = self.peek_one() {
-                  self.advance_one();
-                  break;
-                } else {
-                  self.advance_one();
-                }
-              }
-            }
           } else {
-            while let Some(c) = self.peek_one() {
-              if c != '\n' {
-                self.advance_one();
-              } else {
-                break;
-              }
-            }
+            break;
           }
         }
         continue;
@@ -1244,6 +1438,28 @@ pub fn is_name_char(c: char) -> bool {
   c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '/'
 }
 
+/// Returns the length in bytes of the multi line comment `#{ ... #}` at the start of `code`,
+/// which may contain nested comments, or `None` if it's never closed.
+fn multi_line_comment_len(code: &str) -> Option<usize> {
+  let mut depth = 0;
+  let mut idx = 0;
+  while idx < code.len() {
+    if code[idx..].starts_with("#{") {
+      idx += 2;
+      depth += 1;
+    } else if code[idx..].starts_with("#}") {
+      idx += 2;
+      depth -= 1;
+      if depth == 0 {
+        return Some(idx);
+      }
+    } else {
+      idx += code[idx..].chars().next()?.len_utf8();
+    }
+  }
+  None
+}
+
 pub fn is_num_char(c: char) -> bool {
   "0123456789+-".contains(c)
 }
@@ -1384,36 +1600,17 @@ pub trait ParserCommons<'a>: Parser<'a> {
         char_count += 1;
         continue;
       }
+      if self.starts_with("#{") {
+        char_count += self.advance_multi_line_comment()?;
+        continue;
+      }
       if c == '#' {
-        self.advance_one();
-        char_count += 1;
-        if let Some(c) = self.peek_one() {
-          if c == '{' {
+        while let Some(c) = self.peek_one() {
+          if c != '\n' {
             self.advance_one();
             char_count += 1;
-            while let Some(c) = self.peek_one() {
-              self.advance_one();
-              char_count += 1;
-              if c == '#' {
-                if let Some('}') = self.peek_one() {
-                  self.advance_one();
-                  char_count += 1;
-                  break;
-                } else {
-                  self.advance_one();
-                  char_count += 1;
-                }
-              }
-            }
           } else {
-            while let Some(c) = self.peek_one() {
-              if c != '\n' {
-                self.advance_one();
-                char_count += 1;
-              } else {
-                break;
-              }
-            }
+            break;
           }
         }
         continue;
@@ -1423,6 +1620,84 @@ pub trait ParserCommons<'a>: Parser<'a> {
     Ok(char_count)
   }
 
+  /// Parses a single unicode character, or an escape sequence like `\n` or `\u{1F600}`.
+  /// Invalid escape sequences are reported with the span of the whole sequence.
+  fn parse_char_or_escape(&mut self) -> ParseResult<char> {
+    let ini_idx = *self.index();
+    match self.advance_one() {
+      Some('\\') => match self.advance_one() {
+        Some('u') => {
+          if !self.starts_with("{") {
+            return self.expected("'{'");
+          }
+          self.advance_one();
+          let codepoint = self.take_while(|c| c.is_ascii_hexdigit());
+          if codepoint.is_empty() {
+            return self.expected("hexadecimal digit");
+          }
+          let codepoint = u32::from_str_radix(codepoint, 16).ok();
+          if !self.starts_with("}") {
+            return self.expected("'}'");
+          }
+          self.advance_one();
+          let end_idx = *self.index();
+          match codepoint.and_then(char::from_u32) {
+            Some(chr) => Ok(chr),
+            None => {
+              let escape = &self.input()[ini_idx..end_idx];
+              let msg = format!("Unicode escape '{escape}' is not a valid unicode code point.");
+              self.err_msg_spanned(&msg, ini_idx..end_idx)
+            }
+          }
+        }
+        Some('0') => Ok('\0'),
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('\'') => Ok('\''),
+        Some('\"') => Ok('\"'),
+        Some('\\') => Ok('\\'),
+        Some(chr) => {
+          let end_idx = *self.index();
+          let msg = format!(
+            "Invalid escape sequence '\\{chr}'. The valid escapes are \\n, \\r, \\t, \\0, \\', \\\", \\\\ and \\u{{...}}."
+          );
+          self.err_msg_spanned(&msg, ini_idx..end_idx)
+        }
+        None => self.expected("escaped character"),
+      },
+      Some(chr) => Ok(chr),
+      None => self.expected("character"),
+    }
+  }
+
+  /// Advances the parser past a multi line comment `#{ ... #}`, which may contain nested comments.
+  /// Must be called with the parser at the opening `#{`.
+  /// Returns how many characters were advanced.
+  fn advance_multi_line_comment(&mut self) -> ParseResult<isize> {
+    let ini_idx = *self.index();
+    match multi_line_comment_len(&self.input()[ini_idx..]) {
+      Some(len) => {
+        *self.index() += len;
+        Ok(self.input()[ini_idx..ini_idx + len].chars().count() as isize)
+      }
+      None => Err(self.unterminated_comment_err()),
+    }
+  }
+
+  /// Checks if the parser is at a multi line comment that is never closed.
+  fn starts_with_unterminated_comment(&mut self) -> bool {
+    let idx = *self.index();
+    self.starts_with("#{") && multi_line_comment_len(&self.input()[idx..]).is_none()
+  }
+
+  /// The error for an unterminated multi line comment that starts at the current position.
+  fn unterminated_comment_err(&mut self) -> ParseError {
+    let idx = *self.index();
+    let ctx = highlight_error(idx, idx + 2, self.input());
+    ParseError::new((idx, idx + 2), format!("Unterminated multi line comment.\nLocation:\n{ctx}"))
+  }
+
   /// Skips until the next non-trivia character in the same line.
   fn skip_trivia_inline(&mut self) -> ParseResult<()> {
     self.advance_trivia_inline()?;
@@ -1457,6 +1732,10 @@ pub trait ParserCommons<'a>: Parser<'a> {
 
   /// If the parser result is an error, adds highlighted code context to the message.
   fn with_ctx<T>(&mut self, res: Result<T, impl std::fmt::Display>, span: Range<usize>) -> ParseResult<T> {
+    // `skip_trivia` stops at unterminated comments, so when parsing fails at one, the comment is what's wrong.
+    if res.is_err() && self.starts_with_unterminated_comment() {
+      return Err(self.unterminated_comment_err());
+    }
     res.map_err(|msg| {
       let ctx = highlight_error(span.start, span.end, self.input());
       let msg = format!("{msg}\n{ctx}");
@@ -1499,7 +1778,7 @@ pub trait ParserCommons<'a>: Parser<'a> {
     self.consume_exactly(keyword)?;
     let end_idx = *self.index();
     let input = &self.input()[*self.index()..];
-    let next_is_name = input.chars().next().map_or(false, is_name_char);
+    let next_is_name = input.chars().next().is_some_and(is_name_char);
     if !next_is_name {
       Ok(())
     } else {
@@ -1510,7 +1789,7 @@ pub trait ParserCommons<'a>: Parser<'a> {
   fn starts_with_keyword(&mut self, keyword: &str) -> bool {
     if self.starts_with(keyword) {
       let input = &self.input()[*self.index() + keyword.len()..];
-      let next_is_name = input.chars().next().map_or(false, is_name_char);
+      let next_is_name = input.chars().next().is_some_and(is_name_char);
       !next_is_name
     } else {
       false
@@ -1544,6 +1823,9 @@ pub trait ParserCommons<'a>: Parser<'a> {
       els.push(parser(self)?);
       self.skip_trivia();
       if hard_sep && !(i == min_els - 1 && self.starts_with(end)) {
+        if i == min_els - 1 && !self.starts_with(sep) {
+          return self.expected(&format!("'{sep}' or '{end}'"));
+        }
         self.consume(sep)?;
       } else {
         self.try_consume(sep);
@@ -1552,9 +1834,16 @@ pub trait ParserCommons<'a>: Parser<'a> {
 
     // Consume optional elements
     while !self.try_consume(end) {
+      // Point out the missing delimiter instead of expecting another element.
+      if self.is_eof() && !end.is_empty() {
+        return self.expected(&format!("'{end}'"));
+      }
       els.push(parser(self)?);
       self.skip_trivia();
       if hard_sep && !self.starts_with(end) {
+        if !self.starts_with(sep) {
+          return self.expected(&format!("'{sep}' or '{end}'"));
+        }
         self.consume(sep)?;
       } else {
         self.try_consume(sep);
@@ -1645,40 +1934,81 @@ pub trait ParserCommons<'a>: Parser<'a> {
     Some(opr)
   }
 
-  fn parse_u32(&mut self) -> ParseResult<u32> {
+  /// Consumes a `0x`, `0o` or `0b` prefix (in any case), returning the radix it indicates.
+  /// Numbers without a prefix are decimal.
+  fn parse_radix_prefix(&mut self) -> Radix {
     let radix = match self.peek_many(2) {
-      Some("0x") => {
-        self.advance_many(2);
-        Radix::Hex
-      }
-      Some("0b") => {
-        self.advance_many(2);
-        Radix::Bin
-      }
-      _ => Radix::Dec,
+      Some("0x" | "0X") => Radix::Hex,
+      Some("0o" | "0O") => Radix::Oct,
+      Some("0b" | "0B") => Radix::Bin,
+      _ => return Radix::Dec,
     };
-    let num_str = self.take_while(move |c| c.is_digit(radix as u32) || c == '_');
-    let num_str = num_str.chars().filter(|c| *c != '_').collect::<String>();
+    self.advance_many(2);
+    radix
+  }
 
-    let next_is_hex = self.peek_one().map_or(false, |c| "0123456789abcdefABCDEF".contains(c));
-    if next_is_hex || num_str.is_empty() {
+  fn parse_u32(&mut self) -> ParseResult<u32> {
+    let radix = self.parse_radix_prefix();
+    self.u32_with_radix(radix)
+  }
+
+  fn u32_with_radix(&mut self, radix: Radix) -> ParseResult<u32> {
+    let num_str = self.parse_digits(radix, false)?;
+    u32::from_str_radix(&num_str, radix as u32)
+      .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())
+  }
+
+  /// Parses the non-empty digits of a number in the given radix.
+  /// If `allow_exp`, the digits can be followed by the exponent of a decimal float.
+  fn parse_digits(&mut self, radix: Radix, allow_exp: bool) -> ParseResult<String> {
+    let num_str = self.take_digits(radix)?;
+    let next_is_exp = allow_exp && radix == Radix::Dec && self.starts_with_exponent();
+    let next_is_hex = self.peek_one().is_some_and(|c| "0123456789abcdefABCDEF".contains(c));
+    if (next_is_hex && !next_is_exp) || num_str.is_empty() {
       self.expected(format!("valid {radix} digit").as_str())
     } else {
-      u32::from_str_radix(&num_str, radix as u32)
-        .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())
+      Ok(num_str)
     }
   }
 
-  fn u32_with_radix(&mut self, radix: Radix) -> ParseResult<u32> {
+  /// Takes the digits of a number in the given radix, removing the `_` digit separators.
+  /// A separator must be between two digits, so `1_000` is valid but `_1`, `1_` and `1__0` are not.
+  fn take_digits(&mut self, radix: Radix) -> ParseResult<String> {
+    let ini_idx = *self.index();
     let num_str = self.take_while(move |c| c.is_digit(radix as u32) || c == '_');
-    let num_str = num_str.chars().filter(|c| *c != '_').collect::<String>();
-    let next_is_hex = self.peek_one().map_or(false, |c| "0123456789abcdefABCDEF".contains(c));
-    if next_is_hex || num_str.is_empty() {
-      self.expected(format!("valid {radix} digit").as_str())
-    } else {
-      u32::from_str_radix(&num_str, radix as u32)
-        .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())
+    let bytes = num_str.as_bytes();
+    let misplaced = (0..bytes.len())
+      .find(|&i| bytes[i] == b'_' && (i == 0 || i + 1 == bytes.len() || bytes[i + 1] == b'_'));
+    if let Some(i) = misplaced {
+      let idx = ini_idx + i;
+      return self.err_msg_spanned("Digit separator '_' must be between two digits.", idx..idx + 1);
+    }
+    Ok(num_str.chars().filter(|c| *c != '_').collect())
+  }
+
+  /// Parses a signed number that is a whole element of a parenthesized term, like the `-3` in `(-3)` or `(-3, 4)`.
+  /// Returns `None` without consuming anything if the next element isn't one.
+  fn try_parse_signed_num_elem(&mut self) -> ParseResult<Option<Term>> {
+    let is_signed_num = (self.starts_with("+") || self.starts_with("-"))
+      && self.peek_many(2).is_some_and(|x| x.chars().nth(1).is_some_and(|c| c.is_ascii_digit()));
+    if !is_signed_num {
+      return Ok(None);
+    }
+    // If this isn't a whole element, like the `-9000000` in `(-9000000 x)`, it's an operation instead.
+    let ini_idx = *self.index();
+    if let Ok(val) = self.parse_number() {
+      self.skip_trivia();
+      if self.starts_with(")") || self.starts_with(",") {
+        return Ok(Some(Term::Num { val }));
+      }
     }
+    *self.index() = ini_idx;
+    Ok(None)
+  }
+
+  /// Checks if the parser is at the exponent of a decimal float, like the `e-3` in `1.5e-3`.
+  fn starts_with_exponent(&mut self) -> bool {
+    self.peek_one().is_some_and(|c| c == 'e' || c == 'E')
   }
 
   fn parse_number(&mut self) -> ParseResult<Num> {
@@ -1690,22 +2020,11 @@ pub trait ParserCommons<'a>: Parser<'a> {
     } else {
       None
     };
-    let radix = match self.peek_many(2) {
-      Some("0x") => {
-        self.advance_many(2);
-        Radix::Hex
-      }
-      Some("0b") => {
-        self.advance_many(2);
-        Radix::Bin
-      }
-      _ => Radix::Dec,
-    };
-    let num = self.u32_with_radix(radix)?;
+    let radix = self.parse_radix_prefix();
+    let num_str = self.parse_digits(radix, true)?;
     let frac = if let Some('.') = self.peek_one() {
       self.advance_one();
-      let fra_str = self.take_while(|c| c.is_digit(radix as u32) || c == '_');
-      let fra_str = fra_str.chars().filter(|c| *c != '_').collect::<String>();
+      let fra_str = self.take_digits(radix)?;
       let fra = u32::from_str_radix(&fra_str, radix as u32)
         .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())?;
       let fra = fra as f32 / (radix.to_f32()).powi(fra_str.len() as i32);
@@ -1713,28 +2032,58 @@ pub trait ParserCommons<'a>: Parser<'a> {
     } else {
       None
     };
+    let exp = if radix == Radix::Dec && self.starts_with_exponent() {
+      self.advance_one();
+      let exp_sign = if self.try_consume_exactly("-") {
+        -1
+      } else {
+        self.try_consume_exactly("+");
+        1
+      };
+      let exp_str = self.parse_digits(Radix::Dec, false)?;
+      let exp = exp_str
+        .parse::<i32>()
+        .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())?;
+      Some(exp_sign * exp)
+    } else {
+      None
+    };
 
-    if let Some(frac) = frac {
+    if frac.is_some() || exp.is_some() {
+      let num = u32::from_str_radix(&num_str, radix as u32)
+        .map_err(|e| self.expected_and::<u64>("integer", &e.to_string()).unwrap_err())?;
       let sign = sign.unwrap_or(1);
-      return Ok(Num::F24(sign as f32 * (num as f32 + frac)));
+      let mantissa = num as f32 + frac.unwrap_or(0.0);
+      let num = mantissa * 10f32.powi(exp.unwrap_or(0));
+      // A big enough exponent overflows to infinity, and a small enough one underflows to zero.
+      if num.is_infinite() || (num == 0.0 && mantissa != 0.0) {
+        let msg = "\x1b[1mNumber literal outside of range for F24.\x1b[0m\n\
+                   The exponent makes the number too big or too small to be represented.";
+        let end_idx = *self.index();
+        return self.err_msg_spanned(msg, ini_idx..end_idx);
+      }
+      return Ok(Num::F24(sign as f32 * num));
     }
 
+    // Integers too big for an u32 are also outside of the range of the number types.
+    let num = u32::from_str_radix(&num_str, radix as u32).ok();
     if let Some(sign) = sign {
-      let num = sign * num as i32;
-      if !(-0x00800000..=0x007fffff).contains(&num) {
-        return self.num_range_err(ini_idx, "I24");
+      match num.map(|num| sign * num as i64) {
+        Some(num) if (I24_MIN..=I24_MAX).contains(&num) => Ok(Num::I24(num as i32)),
+        _ => self.num_range_err(ini_idx, "I24", I24_MIN, I24_MAX),
       }
-      Ok(Num::I24(num))
     } else {
-      if num >= 1 << 24 {
-        return self.num_range_err(ini_idx, "U24");
+      match num {
+        Some(num) if num as i64 <= U24_MAX => Ok(Num::U24(num)),
+        _ => self.num_range_err(ini_idx, "U24", 0, U24_MAX),
       }
-      Ok(Num::U24(num))
     }
   }
 
-  fn num_range_err<T>(&mut self, ini_idx: usize, typ: &str) -> ParseResult<T> {
-    let msg = format!("\x1b[1mNumber literal outside of range for {}.\x1b[0m", typ);
+  fn num_range_err<T>(&mut self, ini_idx: usize, typ: &str, min: i64, max: i64) -> ParseResult<T> {
+    let msg = format!(
+      "\x1b[1mNumber literal outside of range for {typ}.\x1b[0m\nThe valid range is from {min} to {max}."
+    );
     let end_idx = *self.index();
     self.err_msg_spanned(&msg, ini_idx..end_idx)
   }
@@ -1818,6 +2167,7 @@ pub trait ParserCommons<'a>: Parser<'a> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Radix {
   Bin = 2,
+  Oct = 8,
   Dec = 10,
   Hex = 16,
 }
@@ -1826,6 +2176,7 @@ impl Radix {
   fn to_f32(self) -> f32 {
     match self {
       Radix::Bin => 2.,
+      Radix::Oct => 8.,
       Radix::Dec => 10.,
       Radix::Hex => 16.,
     }
@@ -1836,6 +2187,7 @@ impl std::fmt::Display for Radix {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       Radix::Bin => write!(f, "binary"),
+      Radix::Oct => write!(f, "octal"),
       Radix::Dec => write!(f, "decimal"),
       Radix::Hex => write!(f, "hexadecimal"),
     }
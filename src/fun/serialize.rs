@@ -0,0 +1,49 @@
+//! Serde support for the types that can't derive it.
+//!
+//! Only compiled with the `serde` feature.
+
+use super::{Name, STRINGS};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Names are serialized as plain strings.
+impl Serialize for Name {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for Name {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    Ok(Name::new(String::deserialize(deserializer)?))
+  }
+}
+
+/// Interned strings are serialized as plain strings.
+pub mod global_string {
+  use super::*;
+  use interner::global::GlobalString;
+
+  pub fn serialize<S: Serializer>(str: &GlobalString, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(str)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GlobalString, D::Error> {
+    Ok(STRINGS.get(String::deserialize(deserializer)?))
+  }
+}
+
+/// HVM nets are serialized in the HVM text syntax.
+pub mod hvm_net {
+  use super::*;
+  use hvm::ast::{CoreParser, Net};
+  use serde::de::Error;
+
+  pub fn serialize<S: Serializer>(net: &Net, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&net.show())
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Net, D::Error> {
+    let net = String::deserialize(deserializer)?;
+    CoreParser::new(&net).parse_net().map_err(|e| D::Error::custom(e.message))
+  }
+}
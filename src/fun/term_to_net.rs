@@ -238,6 +238,7 @@ impl<'t> EncodeTermState<'t, '_> {
         | Term::Str { .. } // Removed in encode_str
         | Term::List { .. } // Removed in encode_list
         | Term::Def { .. } // Removed in earlier pass
+        | Term::Hole { .. } // Replaced in check_holes
         | Term::Err => unreachable!(),
       }
       while let Some((pat, val)) = self.lets.pop() {
@@ -178,7 +178,7 @@ fn simplify_rule_match(
 /// This is useful to avoid unnecessary pattern matching.
 fn irrefutable_fst_row_rule(args: Vec<Name>, rule: Rule, idx: usize, used: &mut BTreeSet<usize>) -> Term {
   let mut term = rule.body;
-  for (arg, pat) in args.into_iter().zip(rule.pats.into_iter()) {
+  for (arg, pat) in args.into_iter().zip(rule.pats) {
     match pat {
       Pattern::Var(None) => {}
       Pattern::Var(Some(var)) => {
@@ -647,7 +647,11 @@ impl std::fmt::Display for DesugarMatchDefErr {
         )
       }
       DesugarMatchDefErr::NumMissingDefault => {
-        write!(f, "Non-exhaustive pattern matching rule. Default case of number type not covered.")
+        write!(
+          f,
+          "Non-exhaustive pattern matching rule. Default case of number type not covered. \
+           Add a rule with a variable or wildcard pattern to handle the remaining numbers."
+        )
       }
       DesugarMatchDefErr::RepeatedBind { bind } => {
         write!(f, "Repeated bind in pattern matching rule: '{bind}'.")
@@ -96,6 +96,7 @@ impl Term {
       | Term::Fold { .. }
       | Term::Bend { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => {}
     })
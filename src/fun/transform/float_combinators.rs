@@ -150,6 +150,7 @@ impl Term {
   fn is_safe(&self, ctx: &mut FloatCombinatorsCtx) -> bool {
     maybe_grow(|| match self {
       Term::Num { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err
       | Term::Fan { .. }
@@ -238,6 +239,7 @@ impl Term {
       | Term::Ask { .. }
       | Term::Open { .. }
       | Term::Def { .. }
+      | Term::Hole { .. }
       | Term::Err => unreachable!(),
     }
   }
@@ -281,6 +283,7 @@ impl Term {
       | Term::Nat { .. }
       | Term::Str { .. }
       | Term::Ref { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => FloatIter::Zero([]),
       Term::With { .. }
@@ -153,6 +153,7 @@ impl Term {
       | Term::Nat { .. }
       | Term::Str { .. }
       | Term::Ref { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => ChildrenIter::Zero([]),
       Term::Mat { .. } => unreachable!("'match' should be removed in earlier pass"),
@@ -159,6 +159,7 @@ impl UniqueNameGenerator {
       | Term::Nat { .. }
       | Term::Str { .. }
       | Term::Ref { .. }
+      | Term::Hole { .. }
       | Term::Era
       | Term::Err => {}
       Term::Open { .. } => unreachable!("'open' should be removed in earlier pass"),
@@ -241,7 +241,13 @@ impl<'a> ImpParser<'a> {
         Ok(Expr::Num { val: self.parse_number()? })
       } else {
         // Var
+        let ini_idx = *self.index();
         let nam = self.labelled(|p| p.parse_var_name(), "expression")?;
+        if nam == "_" {
+          let end_idx = *self.index();
+          return self
+            .err_msg_spanned("'_' is a wildcard pattern and can't be used as a variable.", ini_idx..end_idx);
+        }
         Ok(Expr::Var { nam })
       }
     } else {
@@ -774,6 +780,13 @@ impl<'a> ImpParser<'a> {
   }
 
   fn parse_match_arg(&mut self) -> ParseResult<(Option<Name>, Expr)> {
+    // `_ = arg` is allowed even though `_` can't be used as an expression.
+    self.skip_trivia_inline()?;
+    if self.try_parse_keyword("_") {
+      self.skip_trivia_inline()?;
+      self.consume_exactly("=")?;
+      return Ok((Some(Name::new("_")), self.parse_expr(true, false)?));
+    }
     let ini_idx = *self.index();
     let arg = self.parse_expr(true, false)?;
     let end_idx = *self.index();
@@ -1335,6 +1348,13 @@ impl<'a> Parser<'a> for ImpParser<'a> {
     self.input
   }
 
+  /// Parses a single unicode character, supporting escape sequences.
+  ///
+  /// Override to have our own error messages.
+  fn parse_char(&mut self) -> ParseResult<char> {
+    self.parse_char_or_escape()
+  }
+
   fn index(&mut self) -> &mut usize {
     &mut self.index
   }
@@ -1367,6 +1387,13 @@ impl<'a> Parser<'a> for ImpParser<'a> {
         self.advance_one();
         continue;
       }
+      if self.starts_with("#{") {
+        // Unterminated comments are left in place, to be reported by the next error (see `with_ctx`).
+        if self.advance_multi_line_comment().is_err() {
+          break;
+        }
+        continue;
+      }
       if c == '#' {
         while let Some(c) = self.peek_one() {
           if c != '\n' {
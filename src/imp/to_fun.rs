@@ -49,8 +49,10 @@ impl Definition {
       }
     };
 
-    let rule =
-      fun::Rule { pats: self.args.into_iter().map(|param| fun::Pattern::Var(Some(param))).collect(), body };
+    // `_` is a wildcard, it binds nothing just like in the functional syntax.
+    let pats =
+      self.args.into_iter().map(|param| fun::Pattern::Var(Some(param).filter(|p| p != "_"))).collect();
+    let rule = fun::Rule { pats, body };
 
     let def = fun::Definition {
       name: self.name,
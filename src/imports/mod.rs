@@ -47,6 +47,7 @@ impl ImportCtx {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Import {
   pub path: Name,
   pub imp_type: ImportType,
@@ -61,6 +62,7 @@ impl Import {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImportType {
   Single(Name, Option<Name>),
   List(Vec<(Name, Option<Name>)>),
@@ -78,6 +80,7 @@ impl Display for ImportType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoundSource {
   None,
   File(Name),
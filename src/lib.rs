@@ -90,6 +90,8 @@ pub fn desugar_book(
 
   ctx.check_shared_names();
 
+  ctx.check_repeated_binds();
+
   ctx.set_entrypoint();
 
   ctx.book.encode_adts(opts.adt_encoding);
@@ -110,6 +112,8 @@ pub fn desugar_book(
 
   ctx.book.lift_local_defs();
 
+  ctx.check_holes()?;
+
   ctx.desugar_bend()?;
   ctx.desugar_fold()?;
   ctx.desugar_with_blocks()?;
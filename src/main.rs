@@ -105,7 +105,7 @@ struct RunArgs {
   #[arg(help = "Path to the input file")]
   path: PathBuf,
 
-  #[arg(value_parser = |arg: &str| bend::fun::parser::FunParser::new(Name::new(""), arg, false).parse_term())]
+  #[arg(value_parser = |arg: &str| bend::fun::parser::FunParser::new(Name::new(""), arg, false).parse_standalone_term())]
   arguments: Option<Vec<bend::fun::Term>>,
 }
 
@@ -240,6 +240,7 @@ pub enum WarningArgs {
   RepeatedBind,
   RecursionCycle,
   ImportShadow,
+  Hole,
   MissingMain,
 }
 
@@ -415,6 +416,7 @@ fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts)
         cfg.repeated_bind = severity;
         cfg.recursion_cycle = severity;
         cfg.import_shadow = severity;
+        cfg.hole = severity;
       }
       WarningArgs::IrrefutableMatch => cfg.irrefutable_match = severity,
       WarningArgs::RedundantMatch => cfg.redundant_match = severity,
@@ -423,6 +425,7 @@ fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts)
       WarningArgs::RepeatedBind => cfg.repeated_bind = severity,
       WarningArgs::RecursionCycle => cfg.recursion_cycle = severity,
       WarningArgs::ImportShadow => cfg.import_shadow = severity,
+      WarningArgs::Hole => cfg.hole = severity,
       WarningArgs::MissingMain => cfg.missing_main = severity, // TODO: Should `WarningArgs::All` modify this as well?
     }
   }
@@ -12,8 +12,11 @@ use bend::{
   check_book, compile_book, desugar_book,
   diagnostics::{Diagnostics, DiagnosticsConfig, Severity},
   fun::{
-    load_book::do_parse_book, net_to_term::net_to_term, parser::ParseBook, term_to_net::Labels, Book, Ctx,
-    Name,
+    load_book::{do_parse_book, parse_book_lenient},
+    net_to_term::net_to_term,
+    parser::ParseBook,
+    term_to_net::Labels,
+    Book, Ctx, Name,
   },
   hvm::hvm_book_show_pretty,
   imports::DefaultLoader,
@@ -325,17 +328,48 @@ fn encode_pattern_match() {
 #[test]
 fn parse_file() {
   run_golden_test_dir(function_name!(), &|code, path| {
-    let mut book = parse_book_single_file(code, path)?;
-    let mut ctx = Ctx::new(&mut book, Default::default());
-    ctx.set_entrypoint();
-    ctx.book.encode_adts(AdtEncoding::NumScott);
-    ctx.book.encode_builtins();
-    ctx.resolve_refs().expect("Resolve refs");
-    ctx.prune(false);
-    Ok(book.to_string())
+    let book = parse_book_single_file(code, path)?;
+    Ok(show_parsed_book(book))
+  })
+}
+
+fn show_parsed_book(mut book: Book) -> String {
+  let mut ctx = Ctx::new(&mut book, Default::default());
+  ctx.set_entrypoint();
+  ctx.book.encode_adts(AdtEncoding::NumScott);
+  ctx.book.encode_builtins();
+  ctx.resolve_refs().expect("Resolve refs");
+  ctx.prune(false);
+  book.to_string()
+}
+
+/// Parses a file skipping the definitions with syntax errors, showing both the errors and the parsed book.
+#[test]
+fn parse_file_lenient() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let (book, diagnostics) = parse_book_lenient(code, path, ParseBook::builtins());
+    let book = book.to_fun()?;
+    Ok(format!("{diagnostics}{}", show_parsed_book(book)))
   })
 }
 
+#[cfg(feature = "serde")]
+mod serde {
+  use super::*;
+
+  /// Runs the `parse_file` tests with the parsed book going through a JSON round trip.
+  /// Uses the same snapshots, so serializing must not change the book.
+  #[test]
+  fn parse_file() {
+    run_golden_test_dir(function_name!(), &|code, path| {
+      let book = parse_book_single_file(code, path)?;
+      let json = serde_json::to_string(&book).unwrap();
+      let book: Book = serde_json::from_str(&json).unwrap();
+      Ok(show_parsed_book(book))
+    })
+  }
+}
+
 /// Runs the check command on a file.
 #[test]
 fn check_file() {
@@ -480,7 +514,7 @@ fn examples() -> Result<(), Diagnostics> {
     .min_depth(1)
     .into_iter()
     .filter_map(|e| e.ok())
-    .filter(|e| e.path().extension().map_or(false, |ext| ext == "bend"))
+    .filter(|e| e.path().extension().is_some_and(|ext| ext == "bend"))
   {
     let _guard = RUN_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
     let path = entry.path();